@@ -0,0 +1,225 @@
+//
+// src/auth.rs -- optional JWT bearer-token authentication middleware
+//
+// Copyright (c) 2024 Jeff Garzik
+//
+// This file is part of the pcgtoolssoftware project covered under
+// the MIT License.  For the full license text, please see the LICENSE
+// file in the root directory of this project.
+// SPDX-License-Identifier: MIT
+
+use std::future::{ready, Ready};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::{header, StatusCode};
+use actix_web::{Error, HttpResponse};
+use futures_util::future::LocalBoxFuture;
+use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use serde_derive::{Deserialize, Serialize};
+use serde_json::json;
+
+// JSON db configuration file: optional auth sub-section.  absent entirely
+// when deployments don't want authentication.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AuthConfig {
+    pub secret: String,       // HS256 signing secret for bearer JWTs
+    pub max_age_secs: u64,    // reject tokens whose `iat` is older than this
+}
+
+// claims we expect in an incoming bearer JWT.  `exp` is checked by the
+// `jsonwebtoken` validator itself; `iat` is checked against `max_age_secs`.
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+    exp: usize,
+    iat: Option<usize>,
+}
+
+// helper function, matches the error-envelope shape used elsewhere in kvapp
+fn err_unauthorized() -> HttpResponse {
+    HttpResponse::build(StatusCode::UNAUTHORIZED)
+        .content_type("application/json")
+        .body(
+            json!({
+          "error": {
+             "code" : -401,
+              "message": "unauthorized"}})
+            .to_string(),
+        )
+}
+
+fn validate_bearer_token(req: &ServiceRequest, config: &AuthConfig) -> Result<(), ()> {
+    let header_val = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok());
+    check_auth_header(header_val, config)
+}
+
+// pure logic, split out of `validate_bearer_token` so it's exercisable
+// without standing up a full `ServiceRequest`
+fn check_auth_header(header_val: Option<&str>, config: &AuthConfig) -> Result<(), ()> {
+    let token = header_val.and_then(|h| h.strip_prefix("Bearer ")).ok_or(())?;
+
+    let validation = Validation::new(Algorithm::HS256);
+    let data = decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(config.secret.as_bytes()),
+        &validation,
+    )
+    .map_err(|_e| ())?;
+
+    if let Some(iat) = data.claims.iat {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_e| ())?
+            .as_secs() as usize;
+        if now.saturating_sub(iat) as u64 > config.max_age_secs {
+            return Err(());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+
+    fn test_config() -> AuthConfig {
+        AuthConfig {
+            secret: "test-secret".to_string(),
+            max_age_secs: 3600,
+        }
+    }
+
+    fn make_token(secret: &str, exp: usize, iat: Option<usize>) -> String {
+        let claims = Claims { exp, iat };
+        encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .unwrap()
+    }
+
+    fn now() -> usize {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as usize
+    }
+
+    #[test]
+    fn missing_header_is_rejected() {
+        assert!(check_auth_header(None, &test_config()).is_err());
+    }
+
+    #[test]
+    fn valid_token_is_accepted() {
+        let config = test_config();
+        let token = make_token(&config.secret, now() + 3600, Some(now()));
+        let header_val = format!("Bearer {}", token);
+        assert!(check_auth_header(Some(&header_val), &config).is_ok());
+    }
+
+    #[test]
+    fn expired_token_is_rejected() {
+        let config = test_config();
+        let token = make_token(&config.secret, now() - 3600, Some(now() - 7200));
+        let header_val = format!("Bearer {}", token);
+        assert!(check_auth_header(Some(&header_val), &config).is_err());
+    }
+
+    #[test]
+    fn wrong_secret_is_rejected() {
+        let config = test_config();
+        let token = make_token("wrong-secret", now() + 3600, Some(now()));
+        let header_val = format!("Bearer {}", token);
+        assert!(check_auth_header(Some(&header_val), &config).is_err());
+    }
+
+    #[test]
+    fn token_older_than_max_age_is_rejected() {
+        let config = test_config();
+        let token = make_token(&config.secret, now() + 3600, Some(now() - 7200));
+        let header_val = format!("Bearer {}", token);
+        assert!(check_auth_header(Some(&header_val), &config).is_err());
+    }
+}
+
+/// middleware factory.  validates `Authorization: Bearer <jwt>` on `/api/*`
+/// routes when `config` is present; a no-op (all requests pass through)
+/// when it's `None`, so deployments without an `auth` section are
+/// unaffected.
+pub struct JwtAuth {
+    config: Option<Arc<AuthConfig>>,
+}
+
+impl JwtAuth {
+    pub fn new(config: Option<AuthConfig>) -> Self {
+        JwtAuth {
+            config: config.map(Arc::new),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for JwtAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = JwtAuthMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(JwtAuthMiddleware {
+            service,
+            config: self.config.clone(),
+        }))
+    }
+}
+
+pub struct JwtAuthMiddleware<S> {
+    service: S,
+    config: Option<Arc<AuthConfig>>,
+}
+
+impl<S, B> Service<ServiceRequest> for JwtAuthMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        // no-op when auth isn't configured, and unauthenticated outside /api/*
+        let config = match &self.config {
+            Some(config) if req.path().starts_with("/api/") => config.clone(),
+            _ => {
+                let fut = self.service.call(req);
+                return Box::pin(async move { Ok(fut.await?.map_into_left_body()) });
+            }
+        };
+
+        if validate_bearer_token(&req, &config).is_err() {
+            let (req, _payload) = req.into_parts();
+            return Box::pin(async move {
+                Ok(ServiceResponse::new(req, err_unauthorized()).map_into_right_body())
+            });
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move { Ok(fut.await?.map_into_left_body()) })
+    }
+}
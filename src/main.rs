@@ -12,22 +12,39 @@
 extern crate actix_web;
 extern crate clap;
 
+mod auth;
+
+use auth::{AuthConfig, JwtAuth};
+
 const APPNAME: &'static str = "kvapp";
 const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 const DEF_CFG_FN: &'static str = "cfg-kvapp.json";
 const DEF_BIND_ADDR: &'static str = "127.0.0.1";
 const DEF_BIND_PORT: &'static str = "8080";
 
-use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
 use std::{env, fs};
 
-use actix_web::http::StatusCode;
-use actix_web::{middleware, web, App, HttpResponse, HttpServer};
+use actix_web::http::{header, StatusCode};
+use actix_web::{middleware, web, App, HttpRequest, HttpResponse, HttpServer};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use clap::Parser;
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
 use serde_derive::{Deserialize, Serialize};
 use serde_json::json;
+use sha2::{Digest, Sha256};
 use sled::Db;
 
+// length, in bytes, of a SHA-256 digest
+const SHA256_LEN: usize = 32;
+
+// default and hard-maximum number of keys returned by one /_scan call
+const DEFAULT_SCAN_LIMIT: usize = 100;
+const MAX_SCAN_LIMIT: usize = 1000;
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
@@ -44,22 +61,50 @@ struct Args {
     port: u16,
 }
 
-// JSON db configuration file: database sub-section
-#[derive(Serialize, Deserialize)]
+// JSON db configuration file: named sled tree sub-section.  each entry is
+// exposed as `{db}` in the `/api/{db}/{key}` routes.
+//
+// `"blob"` is reserved and can't be used here: it would collide with the
+// content-addressed `/api/blob` / `/api/blob/{key}` routes, which live
+// outside the `/api/{db}/...` namespace but share its URL shape.
+#[derive(Serialize, Deserialize, Clone)]
 struct DbConfig {
-    name: String, // database short nickname (exposed via JSON HTTP API)
-    path: String, // sled database file path
+    name: String, // tree nickname (exposed via JSON HTTP API as {db})
 }
 
+// db names reserved for fixed, non-tree routes; rejected in `DbConfig.name`
+const RESERVED_DB_NAMES: &[&str] = &["blob"];
+
 // JSON db configuration file: top level
 #[derive(Serialize, Deserialize)]
 struct ServerConfig {
-    database: DbConfig,
+    path: String,           // sled database file path, shared by all trees
+    databases: Vec<DbConfig>, // named sled trees opened at startup
+    auth: Option<AuthConfig>, // absent entirely to disable authentication
 }
 
+// `sled::Db` and `sled::Tree` are already cheap, thread-safe, internally
+// Arc'd handles, so we share them directly via actix's `web::Data` (itself
+// an `Arc`) rather than serializing every request through a `Mutex`.
+#[derive(Clone)]
 struct ServerState {
-    name: String, // db nickname
-    db: Db,       // open db handle
+    db: Db,                            // open db handle, spanning all trees
+    trees: Arc<HashMap<String, sled::Tree>>, // named trees, keyed by {db} nickname
+    metrics_handle: PrometheusHandle,  // renders the Prometheus text exposition
+}
+
+impl ServerState {
+    // look up the named tree, or a 404 error envelope if it's unknown
+    fn tree(&self, db_name: &str) -> Result<&sled::Tree, HttpResponse> {
+        self.trees.get(db_name).ok_or_else(err_not_found)
+    }
+}
+
+// record a single operation's outcome and latency into the installed
+// Prometheus recorder
+fn record_op(op: &'static str, outcome: &'static str, start: Instant) {
+    counter!("kvapp_requests_total", "op" => op, "outcome" => outcome).increment(1);
+    histogram!("kvapp_request_duration_seconds", "op" => op).record(start.elapsed().as_secs_f64());
 }
 
 // helper function, 404 not found
@@ -75,6 +120,32 @@ fn err_not_found() -> HttpResponse {
         )
 }
 
+// helper function, bad request
+fn err_bad_request(message: &str) -> HttpResponse {
+    HttpResponse::build(StatusCode::BAD_REQUEST)
+        .content_type("application/json")
+        .body(
+            json!({
+          "error": {
+             "code" : -400,
+              "message": message}})
+            .to_string(),
+        )
+}
+
+// helper function, precondition failed (conditional write mismatch)
+fn err_precondition_failed() -> HttpResponse {
+    HttpResponse::build(StatusCode::PRECONDITION_FAILED)
+        .content_type("application/json")
+        .body(
+            json!({
+          "error": {
+             "code" : -412,
+              "message": "precondition failed"}})
+            .to_string(),
+        )
+}
+
 // helper function, server error
 fn err_500() -> HttpResponse {
     HttpResponse::build(StatusCode::INTERNAL_SERVER_ERROR)
@@ -104,23 +175,22 @@ fn ok_json(jval: serde_json::Value) -> HttpResponse {
 
 /// simple root index handler, describes our service
 #[get("/")]
-async fn req_index(m_state: web::Data<Arc<Mutex<ServerState>>>) -> HttpResponse {
-    let state = m_state.lock().unwrap();
+async fn req_index(state: web::Data<ServerState>) -> HttpResponse {
+    let mut names: Vec<&String> = state.trees.keys().collect();
+    names.sort();
 
     ok_json(json!({
         "name": APPNAME,
         "version": VERSION,
         "database_info": {
-            "name": state.name
+            "databases": names
         }
     }))
 }
 
 /// example health check.  pings database by calling a db function..
 #[get("/health")]
-async fn req_health(m_state: web::Data<Arc<Mutex<ServerState>>>) -> HttpResponse {
-    let state = m_state.lock().unwrap();
-
+async fn req_health(state: web::Data<ServerState>) -> HttpResponse {
     // query sled db for size-on-disk
     match state.db.size_on_disk() {
         Err(_e) => err_500(),
@@ -128,32 +198,438 @@ async fn req_health(m_state: web::Data<Arc<Mutex<ServerState>>>) -> HttpResponse
     }
 }
 
-/// DELETE data item.  key in URI path.  returned ok as json response
+/// DELETE data item.  db nickname and key in URI path.  returned ok as
+/// json response
 async fn req_delete(
-    m_state: web::Data<Arc<Mutex<ServerState>>>,
-    path: web::Path<String>,
+    state: web::Data<ServerState>,
+    path: web::Path<(String, String)>,
 ) -> HttpResponse {
-    let state = m_state.lock().unwrap();
+    let (db_name, key) = path.into_inner();
+    let tree = match state.tree(&db_name) {
+        Ok(tree) => tree,
+        Err(resp) => return resp,
+    };
+
+    let start = Instant::now();
 
-    // remove record from sled db
-    match state.db.remove(path.clone()) {
+    // remove record from sled tree
+    match tree.remove(key) {
         Ok(optval) => match optval {
-            Some(_val) => ok_json(json!({"result": true})),
-            None => err_not_found(), // db: value not found
+            Some(_val) => {
+                record_op("delete", "ok", start);
+                ok_json(json!({"result": true}))
+            }
+            None => {
+                record_op("delete", "not_found", start);
+                err_not_found() // db: value not found
+            }
         },
-        Err(_e) => err_500(), // db: error
+        Err(_e) => {
+            record_op("delete", "error", start);
+            err_500() // db: error
+        }
     }
 }
 
-/// GET data item.  key in URI path.  returned value as json response
+/// GET data item.  db nickname and key in URI path.  returned value as
+/// binary (application/octet-stream) response.  honors `Range` (partial content) and `If-None-Match`
+/// (conditional GET) request headers.
 async fn req_get(
-    m_state: web::Data<Arc<Mutex<ServerState>>>,
+    req: HttpRequest,
+    state: web::Data<ServerState>,
+    path: web::Path<(String, String)>,
+) -> HttpResponse {
+    let (db_name, key) = path.into_inner();
+    let tree = match state.tree(&db_name) {
+        Ok(tree) => tree,
+        Err(resp) => return resp,
+    };
+
+    let start = Instant::now();
+
+    // query record from sled tree
+    match tree.get(key) {
+        Ok(optval) => match optval {
+            Some(val) => {
+                record_op("get", "ok", start);
+                req_get_response(&req, val.to_vec())
+            }
+            None => {
+                record_op("get", "not_found", start);
+                err_not_found() // db: value not found
+            }
+        },
+        Err(_e) => {
+            record_op("get", "error", start);
+            err_500() // db: error
+        }
+    }
+}
+
+// build the response for a successfully-fetched value, applying
+// conditional-GET and range-request semantics
+fn req_get_response(req: &HttpRequest, val: Vec<u8>) -> HttpResponse {
+    let etag = format!("\"{}\"", sha256_hex(&val));
+
+    // conditional GET: If-None-Match
+    if let Some(inm) = req
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|h| h.to_str().ok())
+    {
+        if inm == etag || inm == "*" {
+            return HttpResponse::NotModified()
+                .insert_header((header::ETAG, etag))
+                .finish();
+        }
+    }
+
+    let total_len = val.len();
+    let range_header = req
+        .headers()
+        .get(header::RANGE)
+        .and_then(|h| h.to_str().ok());
+
+    match parse_range(range_header, total_len) {
+        RangeResult::Full => HttpResponse::Ok()
+            .content_type("application/octet-stream")
+            .insert_header((header::ETAG, etag))
+            .insert_header((header::CACHE_CONTROL, "no-cache"))
+            .insert_header((header::ACCEPT_RANGES, "bytes"))
+            .body(val),
+        RangeResult::Partial(start, end) => HttpResponse::build(StatusCode::PARTIAL_CONTENT)
+            .content_type("application/octet-stream")
+            .insert_header((header::ETAG, etag))
+            .insert_header((header::ACCEPT_RANGES, "bytes"))
+            .insert_header((
+                header::CONTENT_RANGE,
+                format!("bytes {}-{}/{}", start, end, total_len),
+            ))
+            .body(val[start..=end].to_vec()),
+        RangeResult::Unsatisfiable => HttpResponse::build(StatusCode::RANGE_NOT_SATISFIABLE)
+            .insert_header((header::CONTENT_RANGE, format!("bytes */{}", total_len)))
+            .finish(),
+    }
+}
+
+// outcome of parsing a `Range: bytes=...` request header against a value
+// of a known length.  only a single byte range is supported.
+enum RangeResult {
+    Full,
+    Partial(usize, usize), // inclusive start, inclusive end
+    Unsatisfiable,
+}
+
+fn parse_range(range_header: Option<&str>, len: usize) -> RangeResult {
+    let spec = match range_header.and_then(|h| h.strip_prefix("bytes=")) {
+        Some(s) => s,
+        None => return RangeResult::Full,
+    };
+    // multiple ranges aren't supported; only look at the first
+    let spec = match spec.split(',').next() {
+        Some(s) => s.trim(),
+        None => return RangeResult::Full,
+    };
+    let (start_s, end_s) = match spec.split_once('-') {
+        Some(pair) => pair,
+        None => return RangeResult::Full,
+    };
+
+    if len == 0 {
+        return RangeResult::Unsatisfiable;
+    }
+
+    let (start, end) = if start_s.is_empty() {
+        // suffix range: last N bytes
+        match end_s.parse::<usize>() {
+            Ok(suffix_len) if suffix_len > 0 => (len.saturating_sub(suffix_len), len - 1),
+            _ => return RangeResult::Unsatisfiable,
+        }
+    } else {
+        let start = match start_s.parse::<usize>() {
+            Ok(v) => v,
+            Err(_) => return RangeResult::Full,
+        };
+        let end = match end_s.parse::<usize>() {
+            Ok(v) => v,
+            Err(_) if end_s.is_empty() => len - 1,
+            Err(_) => return RangeResult::Full,
+        };
+        (start, end)
+    };
+
+    if start > end || start >= len {
+        return RangeResult::Unsatisfiable;
+    }
+
+    RangeResult::Partial(start, end.min(len - 1))
+}
+
+/// PUT data item.  db nickname and key in URI path, value in body.
+///
+/// Supports optimistic concurrency via `If-Match: <etag>` (write only if
+/// the current value's ETag matches) or `If-None-Match: *` (write only if
+/// the key doesn't yet exist).  Either precondition failing returns `412`.
+/// With neither header, this is an unconditional write.
+async fn req_put(
+    req: HttpRequest,
+    state: web::Data<ServerState>,
+    (path, body): (web::Path<(String, String)>, web::Bytes),
+) -> HttpResponse {
+    let (db_name, key) = path.into_inner();
+    let tree = match state.tree(&db_name) {
+        Ok(tree) => tree,
+        Err(resp) => return resp,
+    };
+
+    let start = Instant::now();
+
+    let if_match = req
+        .headers()
+        .get(header::IF_MATCH)
+        .and_then(|h| h.to_str().ok());
+    let create_only = req
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|h| h.to_str().ok())
+        == Some("*");
+
+    // unconditional write: the common case, and the only one that doesn't
+    // need sled's CAS primitive
+    if if_match.is_none() && !create_only {
+        return match tree.insert(key.as_str(), body.to_vec()) {
+            Ok(_optval) => {
+                record_op("put", "ok", start);
+                ok_json(json!({"result": true}))
+            }
+            Err(_e) => {
+                record_op("put", "error", start);
+                err_500() // db: error
+            }
+        };
+    }
+
+    let expected_old = if create_only {
+        None
+    } else {
+        // ETags aren't reversible, so confirm the match against the
+        // current value and then feed those exact bytes into the CAS --
+        // this also closes the race between the check and the write.
+        let current = match tree.get(key.as_str()) {
+            Ok(v) => v,
+            Err(_e) => {
+                record_op("put", "error", start);
+                return err_500(); // db: error
+            }
+        };
+        let current_etag = current.as_ref().map(|v| format!("\"{}\"", sha256_hex(v)));
+        if current_etag.as_deref() != if_match {
+            record_op("put", "precondition_failed", start);
+            return err_precondition_failed();
+        }
+        current
+    };
+
+    match tree.compare_and_swap(key.as_str(), expected_old, Some(body.to_vec())) {
+        Ok(Ok(())) => {
+            record_op("put", "ok", start);
+            ok_json(json!({"result": true}))
+        }
+        Ok(Err(_cas_err)) => {
+            record_op("put", "precondition_failed", start);
+            err_precondition_failed() // another write raced us
+        }
+        Err(_e) => {
+            record_op("put", "error", start);
+            err_500() // db: error
+        }
+    }
+}
+
+// query parameters accepted by `req_scan`
+#[derive(Deserialize)]
+struct ScanQuery {
+    prefix: Option<String>,
+    start: Option<String>,
+    limit: Option<usize>,
+    values: Option<bool>,
+}
+
+/// list keys in a named tree, in sled's native sort order.  `prefix` filters
+/// to keys sharing that prefix; `start` is a cursor -- the last key returned
+/// by a previous call -- resumed from exclusively; `limit` caps the page
+/// size (default and hard maximum enforced server-side).  pass
+/// `values=true` to also return each key's base64-encoded value.
+#[get("/api/{db}/_scan")]
+async fn req_scan(
+    state: web::Data<ServerState>,
     path: web::Path<String>,
+    query: web::Query<ScanQuery>,
 ) -> HttpResponse {
-    let state = m_state.lock().unwrap();
+    let db_name = path.into_inner();
+    let tree = match state.tree(&db_name) {
+        Ok(tree) => tree,
+        Err(resp) => return resp,
+    };
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SCAN_LIMIT)
+        .clamp(1, MAX_SCAN_LIMIT);
+    let include_values = query.values.unwrap_or(false);
+    let start_bytes = query.start.as_ref().map(|s| s.clone().into_bytes());
+
+    let iter: Box<dyn Iterator<Item = sled::Result<(sled::IVec, sled::IVec)>>> =
+        match &query.prefix {
+            Some(prefix) => Box::new(tree.scan_prefix(prefix.as_bytes())),
+            None => match &start_bytes {
+                Some(start) => Box::new(tree.range((
+                    std::ops::Bound::Excluded(start.clone()),
+                    std::ops::Bound::Unbounded,
+                ))),
+                None => Box::new(tree.iter()),
+            },
+        };
+
+    let mut entries = Vec::new();
+    let mut last_key: Option<Vec<u8>> = None;
+    let mut cursor = None;
+    for item in iter {
+        let (key, val) = match item {
+            Ok(kv) => kv,
+            Err(_e) => return err_500(),
+        };
+
+        // `scan_prefix` doesn't know about `start`, so skip forward past
+        // the cursor manually when both are given
+        if let Some(start) = &start_bytes {
+            if query.prefix.is_some() && key.as_ref() <= start.as_slice() {
+                continue;
+            }
+        }
+
+        if entries.len() >= limit {
+            // the cursor is the last key *returned*, not this lookahead
+            // key, so the next call resumes right after it instead of
+            // skipping it
+            cursor = last_key
+                .as_ref()
+                .map(|k| String::from_utf8_lossy(k).into_owned());
+            break;
+        }
+
+        let key_str = String::from_utf8_lossy(&key).into_owned();
+        if include_values {
+            entries.push(json!({
+                "key": key_str,
+                "value": BASE64.encode(val),
+            }));
+        } else {
+            entries.push(json!(key_str));
+        }
+        last_key = Some(key.to_vec());
+    }
+
+    ok_json(json!({ "keys": entries, "cursor": cursor }))
+}
+
+// a single mutation within a /_batch request.  `value` is required for
+// "put" and ignored for "delete".
+#[derive(Deserialize)]
+struct BatchMutation {
+    op: String,
+    key: String,
+    value: Option<String>,
+}
+
+/// apply a batch of PUT/DELETE mutations to a named tree inside a single
+/// sled transaction, so they commit all-or-nothing.
+#[post("/api/{db}/_batch")]
+async fn req_batch(
+    state: web::Data<ServerState>,
+    path: web::Path<String>,
+    body: web::Json<Vec<BatchMutation>>,
+) -> HttpResponse {
+    let db_name = path.into_inner();
+    let tree = match state.tree(&db_name) {
+        Ok(tree) => tree,
+        Err(resp) => return resp,
+    };
+    let mutations = body.into_inner();
+
+    if mutations
+        .iter()
+        .any(|m| m.op == "put" && m.value.is_none())
+    {
+        return err_bad_request("\"put\" mutation missing required \"value\"");
+    }
+
+    let result: sled::transaction::TransactionResult<(), ()> = tree.transaction(|tx_tree| {
+        for m in &mutations {
+            match m.op.as_str() {
+                "put" => {
+                    // presence already validated above
+                    let value = m.value.clone().unwrap_or_default();
+                    tx_tree.insert(m.key.as_str(), value.into_bytes())?;
+                }
+                "delete" => {
+                    tx_tree.remove(m.key.as_str())?;
+                }
+                _ => {
+                    return Err(sled::transaction::ConflictableTransactionError::Abort(()));
+                }
+            }
+        }
+        Ok(())
+    });
+
+    match result {
+        Ok(()) => ok_json(json!({"result": true})),
+        Err(_e) => err_bad_request("batch transaction failed"),
+    }
+}
+
+// hash `data` with SHA-256 and base58-encode the digest, for use as a
+// content-addressed storage key
+fn blob_key(data: &[u8]) -> String {
+    bs58::encode(Sha256::digest(data)).into_string()
+}
+
+// hash `data` with SHA-256 and hex-encode the digest, for use as a
+// strong ETag
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// POST a content-addressed blob.  body is hashed with SHA-256, and the
+/// base58-encoded digest becomes the storage key.  re-uploading identical
+/// content is a no-op, since the key is derived from the content itself.
+#[post("/api/blob")]
+async fn req_blob_put(state: web::Data<ServerState>, body: web::Bytes) -> HttpResponse {
+    let key = blob_key(&body);
+
+    // insert record into sled db, keyed by content digest
+    match state.db.insert(key.as_str(), body.to_vec()) {
+        Ok(_optval) => ok_json(json!({ "key": key })),
+        Err(_e) => err_500(), // db: error
+    }
+}
+
+/// GET a content-addressed blob.  key in URI path must base58-decode to a
+/// 32-byte SHA-256 digest.
+#[get("/api/blob/{key}")]
+async fn req_blob_get(state: web::Data<ServerState>, path: web::Path<String>) -> HttpResponse {
+    let digest = match bs58::decode(path.as_str()).into_vec() {
+        Ok(bytes) if bytes.len() == SHA256_LEN => bytes,
+        _ => return err_bad_request("invalid base58 SHA-256 key"),
+    };
+    let key = bs58::encode(&digest).into_string();
 
     // query record from sled db
-    match state.db.get(path.clone()) {
+    match state.db.get(key) {
         Ok(optval) => match optval {
             Some(val) => ok_binary(val.to_vec()),
             None => err_not_found(), // db: value not found
@@ -162,18 +638,18 @@ async fn req_get(
     }
 }
 
-/// PUT data item.  key in URI path, value in body
-async fn req_put(
-    m_state: web::Data<Arc<Mutex<ServerState>>>,
-    (path, body): (web::Path<String>, web::Bytes),
-) -> HttpResponse {
-    let state = m_state.lock().unwrap();
+/// Prometheus text-exposition metrics.  sled's `size_on_disk` and key count
+/// are sampled fresh on every scrape, since they're cheap to query and
+/// otherwise go stale between requests.
+#[get("/metrics")]
+async fn req_metrics(state: web::Data<ServerState>) -> HttpResponse {
+    let key_count: usize = state.trees.values().map(|t| t.len()).sum();
+    gauge!("kvapp_db_size_on_disk_bytes").set(state.db.size_on_disk().unwrap_or(0) as f64);
+    gauge!("kvapp_db_key_count").set(key_count as f64);
 
-    // insert record into sled db
-    match state.db.insert(path.as_str(), body.to_vec()) {
-        Ok(_optval) => ok_json(json!({"result": true})),
-        Err(_e) => err_500(), // db: error
-    }
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(state.metrics_handle.render())
 }
 
 #[actix_web::main]
@@ -192,33 +668,63 @@ async fn main() -> std::io::Result<()> {
     // read JSON configuration file
     let cfg_text = fs::read_to_string(args.config)?;
     let server_cfg: ServerConfig = serde_json::from_str(&cfg_text)?;
+    let auth_config = server_cfg.auth.clone();
 
     // configure & open db
     let db_config = sled::Config::default()
-        .path(&server_cfg.database.path)
+        .path(&server_cfg.path)
         .use_compression(false);
     let db = db_config.open().unwrap();
 
-    let srv_state = Arc::new(Mutex::new(ServerState {
-        name: server_cfg.database.name.clone(),
+    // open each configured named tree
+    let mut trees = HashMap::with_capacity(server_cfg.databases.len());
+    for db_cfg in &server_cfg.databases {
+        if RESERVED_DB_NAMES.contains(&db_cfg.name.as_str()) {
+            panic!(
+                "database name \"{}\" is reserved and cannot be used in \"databases\"",
+                db_cfg.name
+            );
+        }
+        let tree = db.open_tree(&db_cfg.name).unwrap();
+        trees.insert(db_cfg.name.clone(), tree);
+    }
+
+    // install the Prometheus recorder once, globally, at startup
+    let metrics_handle = PrometheusBuilder::new()
+        .install_recorder()
+        .expect("failed to install Prometheus recorder");
+
+    let srv_state = ServerState {
         db: db.clone(),
-    }));
+        trees: Arc::new(trees),
+        metrics_handle,
+    };
 
     // configure web server
     println!("Starting http server: {}", bind_pair);
     HttpServer::new(move || {
         App::new()
-            // pass application state to each handler
-            .app_data(web::Data::new(Arc::clone(&srv_state)))
+            // pass application state to each handler.  `ServerState` is
+            // `Clone` (cloning only bumps reference counts on the shared
+            // `Db`, tree map and metrics handle), so each
+            // worker thread gets its own handle with no shared lock.
+            .app_data(web::Data::new(srv_state.clone()))
             // apply default headers
             .wrap(middleware::DefaultHeaders::new().add(("Server", server_hdr.to_string())))
+            // validate bearer JWTs on /api/* when an `auth` section is configured
+            .wrap(JwtAuth::new(auth_config.clone()))
             // enable logger - always register actix-web Logger middleware last
             .wrap(middleware::Logger::default())
             // register our routes
             .service(req_index)
             .service(req_health)
+            .service(req_metrics)
+            .service(req_blob_put)
+            .service(req_blob_get)
+            .service(req_scan)
+            .service(req_batch)
             .service(
-                web::resource("/api/{key}")
+                web::resource("/api/{db}/{key}")
                     .route(web::get().to(req_get))
                     .route(web::put().to(req_put))
                     .route(web::delete().to(req_delete)),
@@ -8,13 +8,15 @@
 // file in the root directory of this project.
 // SPDX-License-Identifier: MIT
 
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
 use serde_json::Value;
+use serial_test::serial;
 use std::env;
 use std::fs;
 use std::path::Path;
 use std::process::{Child, Command};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 const DEF_START_WAIT: u64 = 4;
 const T_VALUE: &'static str = "helloworld";
@@ -59,8 +61,25 @@ fn stop_kvapp_server(mut child: Child) {
     child.kill().expect("Failed to kill kvapp server");
 }
 
+// Like `prepare_environment`, but writes a config with an `auth` section
+// configured instead of copying the fixture (which has none).
+fn prepare_environment_with_auth(secret: &str) {
+    let config = serde_json::json!({
+        "path": "db.kv",
+        "databases": [{"name": "db"}],
+        "auth": {"secret": secret, "max_age_secs": 3600},
+    });
+    fs::write("cfg-kvapp.json", config.to_string()).expect("Failed to write configuration file");
+
+    let db_dir = Path::new("db.kv");
+    if !db_dir.exists() {
+        fs::create_dir(db_dir).expect("Failed to create db.kv directory");
+    }
+}
+
 // Example of an integration test that starts the server, makes a request, and stops the server.
 #[tokio::test]
+#[serial]
 async fn test_kvapp_integration() {
     // Prepare server environment
     prepare_environment();
@@ -101,7 +120,7 @@ async fn test_kvapp_integration() {
 
     // ----------------------------------------------------------------
     // Test: Get non-existent object returns not-found
-    let url = "http://localhost:8080/api/1";
+    let url = "http://localhost:8080/api/db/1";
     let res = client
         .get(url)
         .send()
@@ -178,3 +197,513 @@ async fn test_kvapp_integration() {
     // Stop the server.
     stop_kvapp_server(server_process);
 }
+
+// ----------------------------------------------------------------
+// Test: concurrent PUT/GET traffic.  Fires many requests at once
+// against distinct keys and confirms each one round-trips correctly.
+#[tokio::test]
+#[serial]
+async fn test_kvapp_concurrent_put_get() {
+    const N_CONCURRENT: usize = 32;
+
+    // Prepare server environment
+    prepare_environment();
+
+    // Start the server in the background.
+    let server_process = start_kvapp_server();
+
+    let client = reqwest::Client::new();
+
+    let mut tasks = Vec::with_capacity(N_CONCURRENT);
+    for i in 0..N_CONCURRENT {
+        let client = client.clone();
+        tasks.push(tokio::spawn(async move {
+            let url = format!("http://localhost:8080/api/db/concurrent-{}", i);
+            let value = format!("value-{}", i);
+
+            let res = client
+                .put(&url)
+                .body(value.clone())
+                .send()
+                .await
+                .expect("Failed to send PUT request");
+            assert!(res.status().is_success(), "PUT did not succeed");
+
+            let res = client
+                .get(&url)
+                .send()
+                .await
+                .expect("Failed to send GET request");
+            assert!(res.status().is_success(), "GET did not succeed");
+
+            let body_text = res.text().await.expect("Failed to receive text");
+            assert_eq!(body_text, value);
+        }));
+    }
+
+    for task in tasks {
+        task.await.expect("concurrent PUT/GET task panicked");
+    }
+
+    // Stop the server.
+    stop_kvapp_server(server_process);
+}
+
+// ----------------------------------------------------------------
+// Test: /metrics exposes Prometheus text format and reflects request
+// counters after issuing some traffic.
+#[tokio::test]
+#[serial]
+async fn test_kvapp_metrics() {
+    // Prepare server environment
+    prepare_environment();
+
+    // Start the server in the background.
+    let server_process = start_kvapp_server();
+
+    let client = reqwest::Client::new();
+
+    // Issue a GET against a missing key, so a "not_found" outcome is
+    // guaranteed to show up in the counters.
+    let res = client
+        .get("http://localhost:8080/api/db/metrics-test-missing")
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
+
+    let res = client
+        .get("http://localhost:8080/metrics")
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert!(res.status().is_success(), "Request did not succeed");
+
+    let body_text = res.text().await.expect("Failed to receive text");
+    assert!(body_text.contains("kvapp_requests_total"));
+    assert!(body_text.contains("kvapp_db_size_on_disk_bytes"));
+    assert!(body_text.contains("kvapp_db_key_count"));
+
+    // Stop the server.
+    stop_kvapp_server(server_process);
+}
+
+// ----------------------------------------------------------------
+// Test: content-addressed blob store.  Uploading the same content twice
+// yields the same key, and the key is rejected if it's not a valid
+// base58 SHA-256 digest.
+#[tokio::test]
+#[serial]
+async fn test_kvapp_blob_store() {
+    // Prepare server environment
+    prepare_environment();
+
+    // Start the server in the background.
+    let server_process = start_kvapp_server();
+
+    let client = reqwest::Client::new();
+    let blob_url = "http://localhost:8080/api/blob";
+
+    // Upload content, twice, and confirm both uploads return the same key.
+    let res = client
+        .post(blob_url)
+        .body(T_VALUE)
+        .send()
+        .await
+        .expect("Failed to send POST request");
+    assert!(res.status().is_success(), "POST did not succeed");
+    let json: Value = res.json().await.expect("Failed to deserialize JSON");
+    let key = json["key"].as_str().expect("missing key").to_string();
+
+    let res = client
+        .post(blob_url)
+        .body(T_VALUE)
+        .send()
+        .await
+        .expect("Failed to send POST request");
+    assert!(res.status().is_success(), "POST did not succeed");
+    let json: Value = res.json().await.expect("Failed to deserialize JSON");
+    assert_eq!(json["key"], key, "re-upload of identical content changed key");
+
+    // Fetch the blob back by its content-derived key.
+    let res = client
+        .get(format!("{}/{}", blob_url, key))
+        .send()
+        .await
+        .expect("Failed to send GET request");
+    assert!(res.status().is_success(), "GET did not succeed");
+    let body_text = res.text().await.expect("Failed to receive text");
+    assert_eq!(body_text, T_VALUE);
+
+    // An invalid (non-base58-SHA-256) key is rejected with 400.
+    let res = client
+        .get(format!("{}/not-a-valid-digest", blob_url))
+        .send()
+        .await
+        .expect("Failed to send GET request");
+    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    // Stop the server.
+    stop_kvapp_server(server_process);
+}
+
+// ----------------------------------------------------------------
+// Test: Range requests and conditional GET via ETag/If-None-Match.
+#[tokio::test]
+#[serial]
+async fn test_kvapp_range_and_etag() {
+    // Prepare server environment
+    prepare_environment();
+
+    // Start the server in the background.
+    let server_process = start_kvapp_server();
+
+    let client = reqwest::Client::new();
+    let url = "http://localhost:8080/api/db/range-test";
+
+    // Store a known value.
+    let res = client
+        .put(url)
+        .body(T_VALUE)
+        .send()
+        .await
+        .expect("Failed to send PUT request");
+    assert!(res.status().is_success(), "PUT did not succeed");
+
+    // A plain GET carries an ETag and Accept-Ranges.
+    let res = client
+        .get(url)
+        .send()
+        .await
+        .expect("Failed to send GET request");
+    assert!(res.status().is_success(), "GET did not succeed");
+    assert_eq!(
+        res.headers().get("accept-ranges").map(|v| v.to_str().unwrap()),
+        Some("bytes")
+    );
+    let etag = res
+        .headers()
+        .get("etag")
+        .expect("missing ETag header")
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    // A Range request returns 206 with just the requested slice.
+    let res = client
+        .get(url)
+        .header("Range", "bytes=0-4")
+        .send()
+        .await
+        .expect("Failed to send ranged GET request");
+    assert_eq!(res.status(), reqwest::StatusCode::PARTIAL_CONTENT);
+    let body_text = res.text().await.expect("Failed to receive text");
+    assert_eq!(body_text, &T_VALUE[0..5]);
+
+    // An unsatisfiable range is rejected with 416.
+    let res = client
+        .get(url)
+        .header("Range", "bytes=1000-2000")
+        .send()
+        .await
+        .expect("Failed to send ranged GET request");
+    assert_eq!(res.status(), reqwest::StatusCode::RANGE_NOT_SATISFIABLE);
+
+    // If-None-Match with the current ETag returns 304 with no body.
+    let res = client
+        .get(url)
+        .header("If-None-Match", etag)
+        .send()
+        .await
+        .expect("Failed to send conditional GET request");
+    assert_eq!(res.status(), reqwest::StatusCode::NOT_MODIFIED);
+
+    // Stop the server.
+    stop_kvapp_server(server_process);
+}
+
+// ----------------------------------------------------------------
+// Test: key listing / prefix scan with pagination cursor.
+#[tokio::test]
+#[serial]
+async fn test_kvapp_scan() {
+    // Prepare server environment
+    prepare_environment();
+
+    // Start the server in the background.
+    let server_process = start_kvapp_server();
+
+    let client = reqwest::Client::new();
+
+    // Store a few keys sharing a prefix.
+    for i in 0..5 {
+        let url = format!("http://localhost:8080/api/db/scan-test-{}", i);
+        let res = client
+            .put(&url)
+            .body(format!("value-{}", i))
+            .send()
+            .await
+            .expect("Failed to send PUT request");
+        assert!(res.status().is_success(), "PUT did not succeed");
+    }
+
+    // Scan with a small limit and confirm a cursor is returned.
+    let res = client
+        .get("http://localhost:8080/api/db/_scan?prefix=scan-test-&limit=2")
+        .send()
+        .await
+        .expect("Failed to send scan request");
+    assert!(res.status().is_success(), "scan did not succeed");
+    let json: Value = res.json().await.expect("Failed to deserialize JSON");
+    let keys = json["keys"].as_array().expect("missing keys array");
+    assert_eq!(keys.len(), 2);
+    let cursor = json["cursor"].as_str().expect("missing cursor").to_string();
+
+    // Resume from the cursor and confirm we see the rest.
+    let res = client
+        .get(format!(
+            "http://localhost:8080/api/db/_scan?prefix=scan-test-&start={}",
+            cursor
+        ))
+        .send()
+        .await
+        .expect("Failed to send scan request");
+    assert!(res.status().is_success(), "scan did not succeed");
+    let json: Value = res.json().await.expect("Failed to deserialize JSON");
+    let keys = json["keys"].as_array().expect("missing keys array");
+    assert_eq!(keys.len(), 3);
+
+    // Stop the server.
+    stop_kvapp_server(server_process);
+}
+
+// ----------------------------------------------------------------
+// Test: compare-and-swap PUT via If-Match / If-None-Match.
+#[tokio::test]
+#[serial]
+async fn test_kvapp_conditional_put() {
+    // Prepare server environment
+    prepare_environment();
+
+    // Start the server in the background.
+    let server_process = start_kvapp_server();
+
+    let client = reqwest::Client::new();
+    let url = "http://localhost:8080/api/db/cas-test";
+
+    // Create-only PUT succeeds when the key doesn't yet exist.
+    let res = client
+        .put(url)
+        .header("If-None-Match", "*")
+        .body("v1")
+        .send()
+        .await
+        .expect("Failed to send PUT request");
+    assert!(res.status().is_success(), "create-only PUT did not succeed");
+
+    // A second create-only PUT fails, since the key now exists.
+    let res = client
+        .put(url)
+        .header("If-None-Match", "*")
+        .body("v2")
+        .send()
+        .await
+        .expect("Failed to send PUT request");
+    assert_eq!(res.status(), reqwest::StatusCode::PRECONDITION_FAILED);
+
+    // Fetch the current ETag.
+    let res = client
+        .get(url)
+        .send()
+        .await
+        .expect("Failed to send GET request");
+    let etag = res
+        .headers()
+        .get("etag")
+        .expect("missing ETag header")
+        .to_str()
+        .unwrap()
+        .to_string();
+
+    // A conditional PUT with a stale ETag is rejected.
+    let res = client
+        .put(url)
+        .header("If-Match", "\"stale-etag\"")
+        .body("v3")
+        .send()
+        .await
+        .expect("Failed to send PUT request");
+    assert_eq!(res.status(), reqwest::StatusCode::PRECONDITION_FAILED);
+
+    // A conditional PUT with the current ETag succeeds.
+    let res = client
+        .put(url)
+        .header("If-Match", etag)
+        .body("v3")
+        .send()
+        .await
+        .expect("Failed to send PUT request");
+    assert!(res.status().is_success(), "conditional PUT did not succeed");
+
+    let res = client
+        .get(url)
+        .send()
+        .await
+        .expect("Failed to send GET request");
+    let body_text = res.text().await.expect("Failed to receive text");
+    assert_eq!(body_text, "v3");
+
+    // Stop the server.
+    stop_kvapp_server(server_process);
+}
+
+// ----------------------------------------------------------------
+// Test: batch transaction endpoint applies mutations atomically.
+#[tokio::test]
+#[serial]
+async fn test_kvapp_batch() {
+    // Prepare server environment
+    prepare_environment();
+
+    // Start the server in the background.
+    let server_process = start_kvapp_server();
+
+    let client = reqwest::Client::new();
+
+    // Seed one key that the batch will delete.
+    let res = client
+        .put("http://localhost:8080/api/db/batch-existing")
+        .body(T_VALUE)
+        .send()
+        .await
+        .expect("Failed to send PUT request");
+    assert!(res.status().is_success(), "PUT did not succeed");
+
+    let batch = serde_json::json!([
+        {"op": "put", "key": "batch-a", "value": "value-a"},
+        {"op": "put", "key": "batch-b", "value": "value-b"},
+        {"op": "delete", "key": "batch-existing"},
+    ]);
+
+    let res = client
+        .post("http://localhost:8080/api/db/_batch")
+        .json(&batch)
+        .send()
+        .await
+        .expect("Failed to send batch request");
+    assert!(res.status().is_success(), "batch did not succeed");
+
+    let res = client
+        .get("http://localhost:8080/api/db/batch-a")
+        .send()
+        .await
+        .expect("Failed to send GET request");
+    assert!(res.status().is_success(), "GET did not succeed");
+    assert_eq!(res.text().await.unwrap(), "value-a");
+
+    let res = client
+        .get("http://localhost:8080/api/db/batch-b")
+        .send()
+        .await
+        .expect("Failed to send GET request");
+    assert!(res.status().is_success(), "GET did not succeed");
+    assert_eq!(res.text().await.unwrap(), "value-b");
+
+    let res = client
+        .get("http://localhost:8080/api/db/batch-existing")
+        .send()
+        .await
+        .expect("Failed to send GET request");
+    assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
+
+    // A "put" missing its "value" is rejected before anything is applied.
+    let bad_batch = serde_json::json!([
+        {"op": "put", "key": "batch-c"},
+    ]);
+    let res = client
+        .post("http://localhost:8080/api/db/_batch")
+        .json(&bad_batch)
+        .send()
+        .await
+        .expect("Failed to send batch request");
+    assert_eq!(res.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    let res = client
+        .get("http://localhost:8080/api/db/batch-c")
+        .send()
+        .await
+        .expect("Failed to send GET request");
+    assert_eq!(res.status(), reqwest::StatusCode::NOT_FOUND);
+
+    // Stop the server.
+    stop_kvapp_server(server_process);
+}
+
+// ----------------------------------------------------------------
+// Test: JWT bearer-token middleware, wired into the running app.  `/` and
+// `/health` stay open; `/api/*` rejects missing/invalid tokens with a 401
+// error envelope and accepts a validly signed one.
+#[tokio::test]
+#[serial]
+async fn test_kvapp_jwt_auth() {
+    const SECRET: &'static str = "test-secret";
+
+    // Prepare server environment with an `auth` section configured.
+    prepare_environment_with_auth(SECRET);
+
+    // Start the server in the background.
+    let server_process = start_kvapp_server();
+
+    let client = reqwest::Client::new();
+
+    // `/` stays open without a token.
+    let res = client
+        .get("http://localhost:8080/")
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert!(res.status().is_success(), "Request did not succeed");
+
+    // `/health` stays open without a token.
+    let res = client
+        .get("http://localhost:8080/health")
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert!(res.status().is_success(), "Request did not succeed");
+
+    // `/api/*` without a bearer token is rejected with a 401 error envelope.
+    let res = client
+        .get("http://localhost:8080/api/db/auth-test")
+        .send()
+        .await
+        .expect("Failed to send request");
+    assert_eq!(res.status(), reqwest::StatusCode::UNAUTHORIZED);
+    let json: Value = res.json().await.expect("Failed to deserialize JSON");
+    assert_eq!(json["error"]["code"], -401);
+
+    // A validly signed bearer token is accepted.
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as usize;
+    let claims = serde_json::json!({ "exp": now + 3600, "iat": now });
+    let token = encode(
+        &Header::new(Algorithm::HS256),
+        &claims,
+        &EncodingKey::from_secret(SECRET.as_bytes()),
+    )
+    .expect("Failed to encode JWT");
+
+    let res = client
+        .put("http://localhost:8080/api/db/auth-test")
+        .header("Authorization", format!("Bearer {}", token))
+        .body(T_VALUE)
+        .send()
+        .await
+        .expect("Failed to send PUT request");
+    assert!(res.status().is_success(), "authenticated PUT did not succeed");
+
+    // Stop the server.
+    stop_kvapp_server(server_process);
+}